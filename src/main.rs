@@ -1,14 +1,23 @@
-use chrono::{DateTime, Duration};
-use chrono::{Datelike, Local, TimeZone, Timelike, Utc};
-use chrono_tz::Europe::London;
+use chrono::{DateTime, Duration, LocalResult};
+use chrono::{Datelike, TimeZone, Timelike, Utc};
+use chrono_tz::OffsetName;
 use chrono_tz::Tz;
-use chrono_tz::{OffsetComponents, OffsetName};
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::{char, digit1, space1};
+use nom::combinator::map_res;
+use nom::sequence::separated_pair;
+use nom::IResult;
 use std::cmp::Ordering;
+use std::fs;
+use std::str::FromStr;
 
 #[derive(Default, Debug)]
 struct Intervals {
     work_min: u32,
     rest_min: u32,
+    long_rest_min: u32,
+    cycles_before_long_rest: u32,
 }
 
 #[derive(Default, Debug)]
@@ -24,15 +33,12 @@ enum ScheduleError {
     NotInTheFuture,
     InvalidHourMinSec,
     IntervalGreaterThanAvailableTime,
+    ParseError(String),
+    InvalidLocalTime,
+    IoError(String),
 }
 
 impl Target {
-    pub fn hour(hour: u32) -> Self {
-        Self {
-            hour,
-            ..Default::default()
-        }
-    }
     pub fn hour_min(hour: u32, min: u32) -> Self {
         Self {
             hour,
@@ -42,33 +48,140 @@ impl Target {
     }
 }
 
-fn main() -> Result<(), ScheduleError> {
-    let now_time: DateTime<chrono_tz::Tz> = Utc::now().with_timezone(&London);
-    let target = Target::hour_min(16, 0);
-    let iters = create_schedule(&now_time, &target)?;
-    let mut running_total = 0;
-    for ts in iters.timetable.entries {
+impl FromStr for Target {
+    type Err = ScheduleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (_, (hour, min)) =
+            parse_hh_mm(s.trim()).map_err(|e| ScheduleError::ParseError(e.to_string()))?;
+        Ok(Target::hour_min(hour, min))
+    }
+}
+
+fn parse_number(input: &str) -> IResult<&str, u32> {
+    map_res(digit1, str::parse)(input)
+}
+
+fn parse_hh_mm(input: &str) -> IResult<&str, (u32, u32)> {
+    separated_pair(parse_number, char(':'), parse_number)(input)
+}
+
+// "work 25 rest 5 until 16:00"
+fn parse_work_rest_until(input: &str) -> IResult<&str, (Intervals, Target)> {
+    let (input, _) = tag("work")(input)?;
+    let (input, _) = space1(input)?;
+    let (input, work_min) = parse_number(input)?;
+    let (input, _) = space1(input)?;
+    let (input, _) = tag("rest")(input)?;
+    let (input, _) = space1(input)?;
+    let (input, rest_min) = parse_number(input)?;
+    let (input, _) = space1(input)?;
+    let (input, _) = tag("until")(input)?;
+    let (input, _) = space1(input)?;
+    let (input, (hour, min)) = parse_hh_mm(input)?;
+    Ok((
+        input,
+        (
+            Intervals {
+                work_min,
+                rest_min,
+                ..Default::default()
+            },
+            Target::hour_min(hour, min),
+        ),
+    ))
+}
+
+// "focus until 17:30 in 50/10 blocks"
+fn parse_focus_until_blocks(input: &str) -> IResult<&str, (Intervals, Target)> {
+    let (input, _) = tag("focus until")(input)?;
+    let (input, _) = space1(input)?;
+    let (input, (hour, min)) = parse_hh_mm(input)?;
+    let (input, _) = space1(input)?;
+    let (input, _) = tag("in")(input)?;
+    let (input, _) = space1(input)?;
+    let (input, work_min) = parse_number(input)?;
+    let (input, _) = char('/')(input)?;
+    let (input, rest_min) = parse_number(input)?;
+    let (input, _) = space1(input)?;
+    let (input, _) = tag("blocks")(input)?;
+    Ok((
+        input,
+        (
+            Intervals {
+                work_min,
+                rest_min,
+                ..Default::default()
+            },
+            Target::hour_min(hour, min),
+        ),
+    ))
+}
+
+fn parse_schedule_spec(input: &str) -> Result<(Intervals, Target), ScheduleError> {
+    let (remainder, result) = alt((parse_work_rest_until, parse_focus_until_blocks))(input.trim())
+        .map_err(|e| ScheduleError::ParseError(e.to_string()))?;
+    if !remainder.is_empty() {
+        return Err(ScheduleError::ParseError(format!(
+            "unexpected trailing input: {:?}",
+            remainder
+        )));
+    }
+    Ok(result)
+}
+
+fn main() {
+    if let Err(e) = run() {
+        match e {
+            ScheduleError::ParseError(msg) => eprintln!("could not parse schedule spec: {}", msg),
+            ScheduleError::IoError(msg) => eprintln!("could not write schedule output: {}", msg),
+            other => eprintln!("could not build schedule: {:?}", other),
+        }
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), ScheduleError> {
+    let tz = chrono_tz::Europe::London;
+    let now_time: DateTime<chrono_tz::Tz> = Utc::now().with_timezone(&tz);
+
+    let spec = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "work 25 rest 5 until 16:00".to_string());
+    let (intervals, target) = parse_schedule_spec(&spec)?;
+
+    let schedule = create_schedule(&now_time, &target, &intervals, tz)?;
+    for (start, end, kind) in schedule.iter() {
         println!(
-            "⏱{} {:?}={}",
-            running_total,
-            ts.kind,
-            ts.duration.num_minutes()
+            "⏱{}:{:02} {:?}={}",
+            start.hour(),
+            start.minute(),
+            kind,
+            (end - start).num_minutes()
         );
-        running_total += ts.duration.num_minutes();
     }
+    if schedule.remaining > Duration::zero() {
+        println!(
+            "time left after the last full cycle: {} minute(s)",
+            schedule.remaining.num_minutes()
+        );
+    }
+
+    println!("{}", schedule.render_chart());
 
-    // for time_segment in iters.sequence {
-    //     println!("{:?}", time_segment.duration.num_minutes());
-    // }
+    fs::write("schedule.html", schedule.to_html()).map_err(|e| ScheduleError::IoError(e.to_string()))?;
+    fs::write("schedule.ics", schedule.to_ics(Some(Recurrence::Weekdays)))
+        .map_err(|e| ScheduleError::IoError(e.to_string()))?;
+    println!("wrote schedule.html and schedule.ics");
+
+    let week_end = now_time + Duration::days(6);
+    let week = create_schedule_range(&now_time, &week_end, &target, &intervals, tz)?;
+    if let Some(last_day) = week.last() {
+        fs::write("schedule-week.ics", last_day.to_ics(Some(Recurrence::Daily)))
+            .map_err(|e| ScheduleError::IoError(e.to_string()))?;
+    }
+    println!("planned {} day(s) ahead", week.len());
 
-    // dbg!(diff.num_minutes());
-    // let left_over = diff.num_minutes() / interval_target.num_minutes();
-    // // dbg!(interval_target);
-    // dbg!(left_over);
-    // println!("diff: ({:02}:{:02}:{:02})",
-    //          dur_to_target.num_hours(),
-    //          dur_to_target.num_minutes() % 60,
-    //          dur_to_target.num_seconds() % 60);
     Ok(())
 }
 
@@ -97,72 +210,284 @@ struct Schedule {
     remaining: Duration,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Recurrence {
+    Daily,
+    Weekdays,
+}
+
+impl Schedule {
+    fn iter(&self) -> ScheduleIter<'_> {
+        ScheduleIter {
+            entries: self.timetable.entries.iter(),
+            counter_date: self.start_time,
+        }
+    }
+
+    fn to_ics(&self, recurrence: Option<Recurrence>) -> String {
+        let rrule = recurrence.map(|r| match r {
+            Recurrence::Daily => "RRULE:FREQ=DAILY\r\n",
+            Recurrence::Weekdays => "RRULE:FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR\r\n",
+        });
+
+        let mut events = String::new();
+        for (index, (start, end, kind)) in self.iter().enumerate() {
+            events.push_str("BEGIN:VEVENT\r\n");
+            events.push_str(&format!("UID:{}-{}@schedule-rs\r\n", start.timestamp(), index));
+            // RFC 5545 requires a TZID used on DTSTART/DTEND to be defined by
+            // a VTIMEZONE in this same object. Rather than generate one,
+            // serialize in UTC (trailing Z), which needs no such definition
+            // and every importer resolves unambiguously.
+            events.push_str(&format!(
+                "DTSTART:{}Z\r\n",
+                start.with_timezone(&Utc).format("%Y%m%dT%H%M%S")
+            ));
+            events.push_str(&format!(
+                "DTEND:{}Z\r\n",
+                end.with_timezone(&Utc).format("%Y%m%dT%H%M%S")
+            ));
+            events.push_str(&format!("SUMMARY:{:?}\r\n", kind));
+            if let Some(rrule) = rrule {
+                events.push_str(rrule);
+            }
+            events.push_str("END:VEVENT\r\n");
+        }
+
+        format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//schedule-rs//EN\r\n{}END:VCALENDAR\r\n",
+            events
+        )
+    }
+
+    fn to_html(&self) -> String {
+        let window_start = self.start_time;
+        let window_minutes = (self.end_time - window_start).num_minutes().max(1) as f64;
+
+        let mut blocks = String::new();
+        for (start, end, kind) in self.iter() {
+            let top_pct = (start - window_start).num_minutes() as f64 / window_minutes * 100.0;
+            let height_pct = (end - start).num_minutes() as f64 / window_minutes * 100.0;
+            let (label, color) = match kind {
+                ActivityKind::Work => ("Work", "#4a90d9"),
+                ActivityKind::Rest => ("Rest", "#7fc97f"),
+            };
+            blocks.push_str(&format!(
+                "<div class=\"activity\" style=\"top: {:.2}%; height: {:.2}%; background: {};\">{} {}:{:02}-{}:{:02} ({}m)</div>\n",
+                top_pct,
+                height_pct,
+                color,
+                label,
+                start.hour(),
+                start.minute(),
+                end.hour(),
+                end.minute(),
+                (end - start).num_minutes()
+            ));
+        }
+
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Schedule</title>\n<style>\n  body {{ font-family: sans-serif; margin: 0; padding: 2rem; background: #fafafa; }}\n  .day {{ position: relative; width: 240px; height: 600px; border: 1px solid #ccc; background: #fff; }}\n  .activity {{ position: absolute; left: 0; right: 0; color: #fff; font-size: 0.75rem; padding: 2px 4px; box-sizing: border-box; overflow: hidden; }}\n</style>\n</head>\n<body>\n<div class=\"day\">\n{}</div>\n</body>\n</html>\n",
+            blocks
+        )
+    }
+
+    fn render_chart(&self) -> String {
+        const BAR_WIDTH: i64 = 40;
+
+        let start_hour = self.start_time.hour();
+        let end_hour = self.end_time.hour();
+        let num_hours = (end_hour - start_hour + 1) as usize;
+        let mut work_minutes = vec![0i64; num_hours];
+        let mut rest_minutes = vec![0i64; num_hours];
+
+        for (start, end, kind) in self.iter() {
+            let start_minute = start.hour() as i64 * 60 + start.minute() as i64;
+            let end_minute = end.hour() as i64 * 60 + end.minute() as i64;
+            for hour in start_hour..=end_hour {
+                let hour_start = hour as i64 * 60;
+                let hour_end = hour_start + 60;
+                let overlap = (end_minute.min(hour_end) - start_minute.max(hour_start)).max(0);
+                if overlap > 0 {
+                    let index = (hour - start_hour) as usize;
+                    match kind {
+                        ActivityKind::Work => work_minutes[index] += overlap,
+                        ActivityKind::Rest => rest_minutes[index] += overlap,
+                    }
+                }
+            }
+        }
+
+        let mut out = String::new();
+        for hour in start_hour..=end_hour {
+            let index = (hour - start_hour) as usize;
+            let work_blocks = (work_minutes[index] * BAR_WIDTH / 60) as usize;
+            let rest_blocks = (rest_minutes[index] * BAR_WIDTH / 60) as usize;
+            let idle_blocks = (BAR_WIDTH as usize).saturating_sub(work_blocks + rest_blocks);
+            out.push_str(&format!(
+                "{:02}:00 | {}{}{} | {}m work, {}m rest\n",
+                hour,
+                "█".repeat(work_blocks),
+                "░".repeat(rest_blocks),
+                " ".repeat(idle_blocks),
+                work_minutes[index],
+                rest_minutes[index],
+            ));
+        }
+        out
+    }
+}
+
+struct ScheduleIter<'a> {
+    entries: std::slice::Iter<'a, Activity>,
+    counter_date: DateTime<chrono_tz::Tz>,
+}
+
+impl<'a> Iterator for ScheduleIter<'a> {
+    type Item = (DateTime<chrono_tz::Tz>, DateTime<chrono_tz::Tz>, ActivityKind);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let activity = self.entries.next()?;
+        let start = self.counter_date;
+        let end = start.checked_add_signed(activity.duration)?;
+        self.counter_date = end;
+        Some((start, end, activity.kind.clone()))
+    }
+}
+
 fn create_schedule(
     now_time: &DateTime<chrono_tz::Tz>,
     target: &Target,
+    intervals: &Intervals,
+    tz: Tz,
 ) -> Result<Schedule, ScheduleError> {
-    let interval_target = Duration::minutes(30);
-    let rest_interval = Duration::minutes(5);
-    let (dur_to_target, end_time) = get_duration_until(now_time, target)?;
-    match dur_to_target
-        .num_minutes()
-        .cmp(&interval_target.num_minutes())
-    {
-        Ordering::Less => {
-            println!("available time was less than interval time");
-            Err(ScheduleError::IntervalGreaterThanAvailableTime)
+    // Normalize onto `tz` up front so start_time, end_time, and every instant
+    // ScheduleIter produces from them all share one zone; otherwise passing a
+    // now_time from a different zone than `tz` would mix zones in a Schedule.
+    let now_time = now_time.with_timezone(&tz);
+    let work_interval = Duration::minutes(intervals.work_min as i64);
+    let rest_interval = Duration::minutes(intervals.rest_min as i64);
+    let long_rest_interval = Duration::minutes(intervals.long_rest_min as i64);
+    let (dur_to_target, end_time) = get_duration_until(&now_time, target, tz)?;
+
+    if dur_to_target < work_interval {
+        println!("available time was less than a single work block");
+        return Err(ScheduleError::IntervalGreaterThanAvailableTime);
+    }
+
+    // Walk cycle by cycle, stopping as soon as the *actual* cost of the next
+    // cycle (which varies once a long rest is due) would overshoot the target.
+    let mut entries: Vec<Activity> = Vec::new();
+    let mut elapsed = Duration::zero();
+    let mut cycle: i64 = 0;
+    loop {
+        let is_long_rest = intervals.cycles_before_long_rest > 0
+            && (cycle + 1) % intervals.cycles_before_long_rest as i64 == 0;
+        let rest = if is_long_rest {
+            long_rest_interval
+        } else {
+            rest_interval
+        };
+        let this_cycle = work_interval + rest;
+        if elapsed + this_cycle > dur_to_target {
+            break;
         }
-        Ordering::Equal => {
-            println!("available time was equal to interval time");
-            Err(ScheduleError::IntervalGreaterThanAvailableTime)
+        entries.push(Activity {
+            duration: work_interval,
+            kind: ActivityKind::Work,
+        });
+        entries.push(Activity {
+            duration: rest,
+            kind: ActivityKind::Rest,
+        });
+        elapsed += this_cycle;
+        cycle += 1;
+    }
+
+    let remaining = dur_to_target - elapsed;
+    if remaining > Duration::zero() {
+        // Cap the trailing block at a single work interval so a last-minute
+        // remainder can't produce a work block longer than work_min; any time
+        // left over after that cap becomes a closing rest instead.
+        let final_work = remaining.min(work_interval);
+        entries.push(Activity {
+            duration: final_work,
+            kind: ActivityKind::Work,
+        });
+        let leftover = remaining - final_work;
+        if leftover > Duration::zero() {
+            entries.push(Activity {
+                duration: leftover,
+                kind: ActivityKind::Rest,
+            });
         }
-        Ordering::Greater => {
-            let iterations = dur_to_target.num_minutes() / interval_target.num_minutes();
-            // let end_time = interval_target.num_minutes() * iterations;
-            // let as_d = Duration::minutes(end_time);
-            // let ending: DateTime<chrono_tz::Tz> = now_time + as_d;
-            let remaining_mins = dur_to_target.num_minutes() % interval_target.num_minutes();
-            let mut entries: Vec<Activity> = (0..iterations)
-                .map(|num| {
-                    vec![
-                        Activity {
-                            duration: interval_target - rest_interval,
-                            kind: ActivityKind::Work,
-                        },
-                        Activity {
-                            duration: rest_interval,
-                            kind: ActivityKind::Rest,
-                        },
-                    ]
-                })
-                .flatten()
-                .collect();
-            if remaining_mins > 0 {
-                entries.push(Activity {
-                    duration: Duration::minutes(remaining_mins),
-                    kind: ActivityKind::Work,
-                });
+    }
+
+    Ok(Schedule {
+        timetable: Timetable { entries },
+        start_time: now_time,
+        end_time,
+        remaining,
+    })
+}
+
+fn create_schedule_range(
+    start_date: &DateTime<chrono_tz::Tz>,
+    end_date: &DateTime<chrono_tz::Tz>,
+    target: &Target,
+    intervals: &Intervals,
+    tz: Tz,
+) -> Result<Vec<Schedule>, ScheduleError> {
+    let (hour, min, sec) = (start_date.hour(), start_date.minute(), start_date.second());
+    let mut day = start_date.date_naive();
+    let end_day = end_date.date_naive();
+
+    let mut schedules = Vec::new();
+    while day <= end_day {
+        // Rebuild each day's local start instant from scratch rather than
+        // adding a fixed 24h, so the wall-clock hour doesn't drift across a
+        // DST transition inside the range.
+        let day_start = match tz.with_ymd_and_hms(day.year(), day.month(), day.day(), hour, min, sec) {
+            LocalResult::Single(dt) => dt,
+            LocalResult::Ambiguous(_, _) | LocalResult::None => {
+                return Err(ScheduleError::InvalidLocalTime)
             }
-            Ok(Schedule {
-                timetable: Timetable { entries },
-                start_time: now_time.clone(),
-                end_time: end_time.clone(),
-                remaining: Duration::minutes(remaining_mins),
-            })
-        }
+        };
+        schedules.push(create_schedule(&day_start, target, intervals, tz)?);
+        day = day.succ_opt().ok_or(ScheduleError::InvalidLocalTime)?;
     }
+    Ok(schedules)
 }
 
 fn get_duration_until(
     now_time: &DateTime<chrono_tz::Tz>,
     target: &Target,
+    tz: Tz,
 ) -> Result<(Duration, DateTime<chrono_tz::Tz>), ScheduleError> {
-    // the end time is just the current yr/month/day but with a specific time
-    let end_time = Utc
-        .ymd(now_time.year(), now_time.month(), now_time.day())
-        .with_timezone(&London)
-        .and_hms_opt(target.hour, target.min, target.sec)
-        .ok_or(ScheduleError::InvalidHourMinSec)?;
+    if target.hour > 23 || target.min > 59 || target.sec > 59 {
+        return Err(ScheduleError::InvalidHourMinSec);
+    }
+
+    // the end time is the current yr/month/day but with the target's time,
+    // resolved in the caller's timezone rather than assumed from `now_time`
+    let end_time = match tz.with_ymd_and_hms(
+        now_time.year(),
+        now_time.month(),
+        now_time.day(),
+        target.hour,
+        target.min,
+        target.sec,
+    ) {
+        LocalResult::Single(dt) => dt,
+        LocalResult::Ambiguous(earliest, latest) => {
+            println!(
+                "local time is ambiguous between offsets {} and {} during a DST transition",
+                earliest.offset().abbreviation(),
+                latest.offset().abbreviation()
+            );
+            return Err(ScheduleError::InvalidLocalTime);
+        }
+        LocalResult::None => return Err(ScheduleError::InvalidLocalTime),
+    };
 
     match end_time.cmp(now_time) {
         Ordering::Greater => {
@@ -182,32 +507,35 @@ mod test {
         hr: u32,
         min: u32,
         target: Target,
+        intervals: Intervals,
     ) -> Result<Vec<(ActivityKind, i64, String)>, ScheduleError> {
         let date = Utc.ymd(2022, 8, 10);
         let nine_am = date
             .and_hms(hr - 1, min, 0)
             .with_timezone(&chrono_tz::Europe::London);
-        let schedule = create_schedule(&nine_am, &target)?;
-        let mut elapsed = 0;
-        let mut running_time = schedule.start_time;
-        let mut as_list: Vec<(ActivityKind, i64, String)> = vec![];
-        for x in &schedule.timetable.entries {
-            as_list.push((
-                x.kind.clone(),
-                x.duration.num_minutes(),
-                format!("{}:{:02}", running_time.hour(), running_time.minute()),
-            ));
-            let curr_time = running_time.checked_add_signed(x.duration);
-            if let Some(curr_time) = curr_time {
-                running_time = curr_time;
-            }
-        }
+        let schedule = create_schedule(&nine_am, &target, &intervals, chrono_tz::Europe::London)?;
+        let as_list: Vec<(ActivityKind, i64, String)> = schedule
+            .iter()
+            .map(|(start, end, kind)| {
+                (
+                    kind,
+                    (end - start).num_minutes(),
+                    format!("{}:{:02}", start.hour(), start.minute()),
+                )
+            })
+            .collect();
         Ok(as_list)
     }
 
     #[test]
     fn test_schedule() -> Result<(), ScheduleError> {
-        let schedule_entries = for_time_and_target(9, 0, Target::hour_min(11, 30))?;
+        let intervals = Intervals {
+            work_min: 25,
+            rest_min: 5,
+            long_rest_min: 15,
+            cycles_before_long_rest: 0,
+        };
+        let schedule_entries = for_time_and_target(9, 0, Target::hour_min(11, 30), intervals)?;
         let expected = vec![
             (Work, 25, "9:00"),
             (Rest, 5, "9:25"),
@@ -228,4 +556,222 @@ mod test {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_schedule_with_long_rest() -> Result<(), ScheduleError> {
+        let intervals = Intervals {
+            work_min: 25,
+            rest_min: 5,
+            long_rest_min: 15,
+            cycles_before_long_rest: 2,
+        };
+        let schedule_entries = for_time_and_target(9, 0, Target::hour_min(11, 30), intervals)?;
+        let expected = vec![
+            (Work, 25, "9:00"),
+            (Rest, 5, "9:25"),
+            (Work, 25, "9:30"),
+            (Rest, 15, "9:55"),
+            (Work, 25, "10:10"),
+            (Rest, 5, "10:35"),
+            (Work, 25, "10:40"),
+            (Rest, 15, "11:05"),
+            (Work, 10, "11:20"),
+        ];
+        assert_eq!(schedule_entries.len(), expected.len());
+        for (a, b) in schedule_entries.iter().zip(expected.iter()) {
+            assert_eq!(a.0, b.0);
+            assert_eq!(a.1, b.1);
+            assert_eq!(a.2, b.2);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_schedule_caps_trailing_work_block_at_work_min() -> Result<(), ScheduleError> {
+        let intervals = Intervals {
+            work_min: 25,
+            rest_min: 5,
+            long_rest_min: 15,
+            cycles_before_long_rest: 0,
+        };
+        let schedule_entries = for_time_and_target(9, 0, Target::hour_min(9, 58), intervals)?;
+        let expected = vec![
+            (Work, 25, "9:00"),
+            (Rest, 5, "9:25"),
+            (Work, 25, "9:30"),
+            (Rest, 3, "9:55"),
+        ];
+        assert_eq!(schedule_entries.len(), expected.len());
+        for (a, b) in schedule_entries.iter().zip(expected.iter()) {
+            assert_eq!(a.0, b.0);
+            assert_eq!(a.1, b.1);
+            assert_eq!(a.2, b.2);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_html() -> Result<(), ScheduleError> {
+        let date = Utc.ymd(2022, 8, 10);
+        let nine_am = date.and_hms(8, 0, 0).with_timezone(&chrono_tz::Europe::London);
+        let intervals = Intervals {
+            work_min: 25,
+            rest_min: 5,
+            long_rest_min: 15,
+            cycles_before_long_rest: 0,
+        };
+        let schedule = create_schedule(
+            &nine_am,
+            &Target::hour_min(11, 30),
+            &intervals,
+            chrono_tz::Europe::London,
+        )?;
+        let html = schedule.to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Work 9:00-9:25"));
+        assert!(html.contains("class=\"activity\""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_schedule_range() -> Result<(), ScheduleError> {
+        let start = Utc
+            .ymd(2022, 8, 10)
+            .and_hms(8, 0, 0)
+            .with_timezone(&chrono_tz::Europe::London);
+        let end = Utc
+            .ymd(2022, 8, 11)
+            .and_hms(8, 0, 0)
+            .with_timezone(&chrono_tz::Europe::London);
+        let intervals = Intervals {
+            work_min: 25,
+            rest_min: 5,
+            long_rest_min: 15,
+            cycles_before_long_rest: 0,
+        };
+        let schedules = create_schedule_range(
+            &start,
+            &end,
+            &Target::hour_min(11, 30),
+            &intervals,
+            chrono_tz::Europe::London,
+        )?;
+        assert_eq!(schedules.len(), 2);
+        assert_eq!(schedules[0].start_time.day(), 10);
+        assert_eq!(schedules[1].start_time.day(), 11);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_ics() -> Result<(), ScheduleError> {
+        let date = Utc.ymd(2022, 8, 10);
+        let nine_am = date.and_hms(8, 0, 0).with_timezone(&chrono_tz::Europe::London);
+        let intervals = Intervals {
+            work_min: 25,
+            rest_min: 5,
+            long_rest_min: 15,
+            cycles_before_long_rest: 0,
+        };
+        let schedule = create_schedule(
+            &nine_am,
+            &Target::hour_min(11, 30),
+            &intervals,
+            chrono_tz::Europe::London,
+        )?;
+
+        let ics = schedule.to_ics(None);
+        assert!(ics.starts_with("BEGIN:VCALENDAR"));
+        assert!(ics.contains("DTSTART:20220810T080000Z"));
+        assert!(ics.contains("SUMMARY:Work"));
+        assert!(!ics.contains("RRULE"));
+
+        let ics = schedule.to_ics(Some(Recurrence::Weekdays));
+        assert!(ics.contains("RRULE:FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_schedule_respects_configured_timezone() -> Result<(), ScheduleError> {
+        let date = Utc.ymd(2022, 8, 10);
+        let nine_am_london = date.and_hms(8, 0, 0).with_timezone(&chrono_tz::Europe::London);
+        let intervals = Intervals {
+            work_min: 25,
+            rest_min: 5,
+            long_rest_min: 15,
+            cycles_before_long_rest: 0,
+        };
+        let schedule = create_schedule(
+            &nine_am_london,
+            &Target::hour_min(11, 30),
+            &intervals,
+            chrono_tz::America::New_York,
+        )?;
+        // 11:30 in New York is several hours after 11:30 in London, so the
+        // duration to target is larger and more work/rest cycles fit in.
+        assert_eq!(schedule.end_time.hour(), 11);
+        assert!(schedule.timetable.entries.len() > 10);
+        // now_time is normalized onto `tz` up front, so start_time and
+        // end_time must agree on zone rather than mixing London and New York.
+        assert_eq!(schedule.start_time.timezone(), chrono_tz::America::New_York);
+        assert_eq!(schedule.end_time.timezone(), chrono_tz::America::New_York);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_chart() -> Result<(), ScheduleError> {
+        let date = Utc.ymd(2022, 8, 10);
+        let nine_am = date.and_hms(8, 0, 0).with_timezone(&chrono_tz::Europe::London);
+        let intervals = Intervals {
+            work_min: 25,
+            rest_min: 5,
+            long_rest_min: 15,
+            cycles_before_long_rest: 0,
+        };
+        let schedule = create_schedule(
+            &nine_am,
+            &Target::hour_min(11, 30),
+            &intervals,
+            chrono_tz::Europe::London,
+        )?;
+        let chart = schedule.render_chart();
+        let lines: Vec<&str> = chart.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("09:00"));
+        assert!(lines[0].contains("█"));
+        assert!(lines[2].starts_with("11:00"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_schedule_spec_work_rest_until() -> Result<(), ScheduleError> {
+        let (intervals, target) = parse_schedule_spec("work 25 rest 5 until 16:00")?;
+        assert_eq!(intervals.work_min, 25);
+        assert_eq!(intervals.rest_min, 5);
+        assert_eq!(target.hour, 16);
+        assert_eq!(target.min, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_schedule_spec_rejects_trailing_input() {
+        let result = parse_schedule_spec("work 25 rest 5 until 16:00 and then some");
+        assert!(matches!(result, Err(ScheduleError::ParseError(_))));
+    }
+
+    #[test]
+    fn test_parse_schedule_spec_focus_until_blocks() -> Result<(), ScheduleError> {
+        let (intervals, target) = parse_schedule_spec("focus until 17:30 in 50/10 blocks")?;
+        assert_eq!(intervals.work_min, 50);
+        assert_eq!(intervals.rest_min, 10);
+        assert_eq!(target.hour, 17);
+        assert_eq!(target.min, 30);
+        Ok(())
+    }
+
+    #[test]
+    fn test_target_from_str() {
+        let target: Target = "16:05".parse().expect("valid HH:MM");
+        assert_eq!(target.hour, 16);
+        assert_eq!(target.min, 5);
+    }
 }